@@ -1,6 +1,12 @@
-use std::{path::Path, ffi::CString, ptr::null};
+#[cfg(feature = "std")]
+use std::{path::Path, ffi::CString};
 
-use crate::runtime::base::Allocator;
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::runtime::base::{Allocator, IreeAllocator};
 
 use super::{base::StringView, error::RuntimeError};
 use iree_sys::runtime as sys;
@@ -10,7 +16,7 @@ use super::{base, hal::DriverRegistry};
 
 pub struct InstanceOptions<'a> {
     ctx: sys::iree_runtime_instance_options_t,
-    marker: std::marker::PhantomData<&'a mut DriverRegistry>,
+    marker: core::marker::PhantomData<&'a mut DriverRegistry>,
 }
 
 impl<'a> InstanceOptions<'a> {
@@ -23,7 +29,7 @@ impl<'a> InstanceOptions<'a> {
         }
         Self {
             ctx: options,
-            marker: std::marker::PhantomData,
+            marker: core::marker::PhantomData,
         }
     }
 
@@ -37,6 +43,9 @@ impl<'a> InstanceOptions<'a> {
 
 pub struct Instance {
     ctx: *mut sys::iree_runtime_instance_t,
+    // Keeps the host allocator alive for the lifetime of the instance, since
+    // IREE retains the `iree_allocator_t` handed to it at creation.
+    _allocator: Allocator,
 }
 
 // Instance is thread-safe.
@@ -45,28 +54,52 @@ unsafe impl Sync for Instance {}
 
 impl Instance {
     pub fn new(options: &InstanceOptions) -> Result<Self, RuntimeError> {
+        Self::create(options, Allocator::get_global())
+    }
+
+    // Creates an instance backed by a user allocator instead of the global
+    // one, so callers can plug arena/bump/pool allocators for deterministic
+    // inference.
+    // Instance is Send + Sync, so A must be too: trait_allocator_ctl can be
+    // called concurrently from multiple threads through it.
+    pub fn new_with_allocator<A: IreeAllocator + Send + Sync + 'static>(
+        options: &InstanceOptions,
+        allocator: A,
+    ) -> Result<Self, RuntimeError> {
+        Self::new_with_allocator_aligned::<A, 16>(options, allocator)
+    }
+
+    // Like new_with_allocator, but lets the caller pick the alignment of the
+    // size-prefix header for allocators that need over-aligned device-staging
+    // buffers.
+    pub fn new_with_allocator_aligned<A: IreeAllocator + Send + Sync + 'static, const ALIGN: usize>(
+        options: &InstanceOptions,
+        allocator: A,
+    ) -> Result<Self, RuntimeError> {
+        Self::create(options, Allocator::from_trait::<A, ALIGN>(allocator))
+    }
+
+    fn create(options: &InstanceOptions, allocator: Allocator) -> Result<Self, RuntimeError> {
         debug!("Creating instance...");
-        let mut out_ptr = std::ptr::null_mut();
+        let mut out_ptr = core::ptr::null_mut();
         base::Status::from_raw(unsafe {
             sys::iree_runtime_instance_create(
                 &options.ctx,
-                base::Allocator::get_global().ctx,
+                allocator.ctx,
                 &mut out_ptr as *mut *mut sys::iree_runtime_instance_t,
             )
         })
         .to_result()?;
         debug!("Instance created!, out_ptr: {:p}", out_ptr);
-        Ok(Self { ctx: out_ptr })
+        Ok(Self {
+            ctx: out_ptr,
+            _allocator: allocator,
+        })
     }
 
     fn get_host_allocator(&self) -> base::Allocator {
         let out_ptr = unsafe { sys::iree_runtime_instance_host_allocator(self.ctx) };
-        base::Allocator {
-            ctx: sys::iree_allocator_t {
-                self_: std::ptr::null_mut(),
-                ctl: out_ptr.ctl,
-            },
-        }
+        base::Allocator::from_raw(out_ptr)
     }
 
     // pub fn get_vm_instance(&self) -> vm::Instance {
@@ -78,7 +111,7 @@ impl Instance {
     }
 
     pub fn try_create_default_device(&self, name: &str) -> Result<super::hal::Device, RuntimeError> {
-        let mut out_ptr = std::ptr::null_mut();
+        let mut out_ptr = core::ptr::null_mut();
         let status = unsafe {
             sys::iree_runtime_instance_try_create_default_device(
                 self.ctx,
@@ -126,7 +159,12 @@ impl Default for SessionOptions {
 pub struct Session<'a, 'b> {
     ctx: *mut sys::iree_runtime_session_t,
     _instance: &'a Instance,
-    device_marker: std::marker::PhantomData<&'b mut super::hal::Device>,
+    // Keeps a user-supplied host allocator alive for the session's lifetime.
+    _allocator: Allocator,
+    // Keeps the allocator passed to each append_module_from_memory call alive
+    // for the session's lifetime, since IREE retains it for the module.
+    _module_allocators: Vec<Allocator>,
+    device_marker: core::marker::PhantomData<&'b mut super::hal::Device>,
 }
 
 // Session is thread-compatible.
@@ -138,8 +176,38 @@ impl<'a, 'b> Session<'a, 'b> {
         options: &SessionOptions,
         device: &'b super::hal::Device,
     ) -> Result<Self, RuntimeError> {
-        let mut out_ptr = std::ptr::null_mut();
-        let allocator = instance.get_host_allocator();
+        Self::create(instance, options, device, instance.get_host_allocator())
+    }
+
+    // Session is Send (not Sync), so A only needs to be Send.
+    pub fn create_with_device_and_allocator<A: IreeAllocator + Send + 'static>(
+        instance: &'a Instance,
+        options: &SessionOptions,
+        device: &'b super::hal::Device,
+        allocator: A,
+    ) -> Result<Self, RuntimeError> {
+        Self::create_with_device_and_allocator_aligned::<A, 16>(instance, options, device, allocator)
+    }
+
+    // Like create_with_device_and_allocator, but lets the caller pick the
+    // alignment of the size-prefix header, for allocators that need
+    // over-aligned device-staging buffers.
+    pub fn create_with_device_and_allocator_aligned<A: IreeAllocator + Send + 'static, const ALIGN: usize>(
+        instance: &'a Instance,
+        options: &SessionOptions,
+        device: &'b super::hal::Device,
+        allocator: A,
+    ) -> Result<Self, RuntimeError> {
+        Self::create(instance, options, device, Allocator::from_trait::<A, ALIGN>(allocator))
+    }
+
+    fn create(
+        instance: &'a Instance,
+        options: &SessionOptions,
+        device: &'b super::hal::Device,
+        allocator: Allocator,
+    ) -> Result<Self, RuntimeError> {
+        let mut out_ptr = core::ptr::null_mut();
         let status = unsafe {
             sys::iree_runtime_session_create_with_device(
                 instance.ctx,
@@ -155,13 +223,15 @@ impl<'a, 'b> Session<'a, 'b> {
         Ok(Self {
             ctx: out_ptr,
             _instance: instance,
-            device_marker: std::marker::PhantomData,
+            _allocator: allocator,
+            _module_allocators: Vec::new(),
+            device_marker: core::marker::PhantomData,
         })
     }
 
     fn get_allocator(&self) -> base::Allocator {
         let out = unsafe { sys::iree_runtime_session_host_allocator(self.ctx) };
-        base::Allocator { ctx: out }
+        base::Allocator::from_raw(out)
     }
 
     // pub fn get_device(&self) -> super::hal::Device {
@@ -180,20 +250,46 @@ impl<'a, 'b> Session<'a, 'b> {
     // pub fn append_module(&self, module: &Module) -> Result<(), RuntimeError> {
     // TODO: implement this
     
-    pub unsafe fn append_module_from_memory(&self, flatbuffer_data: &'b [u8]) -> Result<(), RuntimeError> {
+    pub unsafe fn append_module_from_memory(&mut self, flatbuffer_data: &'b [u8]) -> Result<(), RuntimeError> {
+        self.append_module_from_memory_impl(flatbuffer_data, base::Allocator::null_allocator())
+    }
+
+    // Like append_module_from_memory, but backed by a user allocator instead
+    // of the null allocator. Session is Send (not Sync), so A only needs to
+    // be Send.
+    pub unsafe fn append_module_from_memory_with_allocator<A: IreeAllocator + Send + 'static>(
+        &mut self,
+        flatbuffer_data: &'b [u8],
+        allocator: A,
+    ) -> Result<(), RuntimeError> {
+        self.append_module_from_memory_impl(flatbuffer_data, base::Allocator::from_trait::<A, 16>(allocator))
+    }
+
+    unsafe fn append_module_from_memory_impl(
+        &mut self,
+        flatbuffer_data: &'b [u8],
+        allocator: base::Allocator,
+    ) -> Result<(), RuntimeError> {
         debug!("Appending bytecode module from memory...");
         let const_byte_span = base::ConstByteSpan::from(flatbuffer_data);
         base::Status::from_raw(unsafe {
             sys::iree_runtime_session_append_bytecode_module_from_memory(
                 self.ctx,
                 const_byte_span.ctx,
-                base::Allocator::null_allocator().ctx,
+                allocator.ctx,
             )
         })
         .to_result()
-        .map_err(|e| RuntimeError::StatusError(e))
+        .map_err(|e| RuntimeError::StatusError(e))?;
+        self._module_allocators.push(allocator);
+        Ok(())
     }
 
+    // Only available with the `std` feature; bare-metal targets should load
+    // flash-resident flatbuffers through append_module_from_memory instead.
+    // iree_runtime_session_append_bytecode_module_from_file takes no
+    // allocator in the C API, so there's no allocator to thread through here.
+    #[cfg(feature = "std")]
     pub unsafe fn append_module_from_file(&self, path: &Path) -> Result<(), RuntimeError> {
         debug!("Appending bytecode module from file...");
         let cstr = CString::new(path.to_str().unwrap()).unwrap();
@@ -1,11 +1,34 @@
-use std::{ffi::c_void, fmt::Display};
+use core::{
+    alloc::Layout,
+    ffi::c_void,
+    fmt::Display,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
+
+#[cfg(feature = "std")]
+use std::alloc as rust_alloc;
+#[cfg(not(feature = "std"))]
+use alloc::alloc as rust_alloc;
+
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
 
 use iree_sys::runtime as sys;
 use log::trace;
 
 pub struct ByteSpan<'a> {
     pub(crate) ctx: sys::iree_byte_span_t,
-    marker: std::marker::PhantomData<&'a mut [u8]>,
+    marker: core::marker::PhantomData<&'a mut [u8]>,
 }
 
 impl<'a> From<&'a mut [u8]> for ByteSpan<'a> {
@@ -16,20 +39,20 @@ impl<'a> From<&'a mut [u8]> for ByteSpan<'a> {
         };
         Self {
             ctx: byte_span,
-            marker: std::marker::PhantomData,
+            marker: core::marker::PhantomData,
         }
     }
 }
 
 impl<'a> From<ByteSpan<'a>> for &'a mut [u8] {
     fn from(byte_span: ByteSpan<'a>) -> Self {
-        unsafe { std::slice::from_raw_parts_mut(byte_span.ctx.data, byte_span.ctx.data_length) }
+        unsafe { core::slice::from_raw_parts_mut(byte_span.ctx.data, byte_span.ctx.data_length) }
     }
 }
 
 pub struct ConstByteSpan<'a> {
     pub ctx: sys::iree_const_byte_span_t,
-    marker: std::marker::PhantomData<&'a [u8]>,
+    marker: core::marker::PhantomData<&'a [u8]>,
 }
 
 impl<'a> From<&'a [u8]> for ConstByteSpan<'a> {
@@ -40,26 +63,26 @@ impl<'a> From<&'a [u8]> for ConstByteSpan<'a> {
         };
         Self {
             ctx: byte_span,
-            marker: std::marker::PhantomData,
+            marker: core::marker::PhantomData,
         }
     }
 }
 
 impl<'a> From<ConstByteSpan<'a>> for &'a [u8] {
     fn from(byte_span: ConstByteSpan<'a>) -> Self {
-        unsafe { std::slice::from_raw_parts(byte_span.ctx.data, byte_span.ctx.data_length) }
+        unsafe { core::slice::from_raw_parts(byte_span.ctx.data, byte_span.ctx.data_length) }
     }
 }
 
 pub struct StringView<'a> {
     pub ctx: sys::iree_string_view_t,
-    marker: std::marker::PhantomData<&'a mut str>,
+    marker: core::marker::PhantomData<&'a mut str>,
 }
 
 impl Display for StringView<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", unsafe {
-            std::str::from_utf8_unchecked(std::slice::from_raw_parts(
+            core::str::from_utf8_unchecked(core::slice::from_raw_parts(
                 self.ctx.data as *const u8,
                 self.ctx.size,
             ))
@@ -75,7 +98,7 @@ impl<'a> From<&'a str> for StringView<'a> {
         };
         Self {
             ctx: string_view,
-            marker: std::marker::PhantomData,
+            marker: core::marker::PhantomData,
         }
     }
 }
@@ -83,7 +106,7 @@ impl<'a> From<&'a str> for StringView<'a> {
 impl<'a> From<StringView<'a>> for &'a str {
     fn from(string_view: StringView<'a>) -> Self {
         unsafe {
-            std::str::from_utf8_unchecked_mut(std::slice::from_raw_parts_mut(
+            core::str::from_utf8_unchecked_mut(core::slice::from_raw_parts_mut(
                 string_view.ctx.data as *mut u8,
                 string_view.ctx.size,
             ))
@@ -91,25 +114,233 @@ impl<'a> From<StringView<'a>> for &'a str {
     }
 }
 
+// Host allocator plugged into the IREE runtime, following the
+// MALLOC/CALLOC/REALLOC/FREE commands of iree_allocator_t. Unsafe because the
+// pointers it returns are handed straight to the C runtime.
+pub unsafe trait IreeAllocator {
+    fn alloc(&self, layout: Layout) -> *mut u8;
+
+    // ptr must have been produced by a previous alloc/realloc on self with old_layout.
+    unsafe fn realloc(&self, ptr: *mut u8, old_layout: Layout, new_layout: Layout) -> *mut u8;
+
+    // ptr must have been produced by a previous alloc/realloc on self with layout.
+    unsafe fn free(&self, ptr: *mut u8, layout: Layout);
+}
+
+// Routes through Rust's global allocator.
+pub struct Global;
+
+unsafe impl IreeAllocator for Global {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        unsafe { rust_alloc::alloc(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, old_layout: Layout, new_layout: Layout) -> *mut u8 {
+        rust_alloc::realloc(ptr, old_layout, new_layout.size())
+    }
+
+    unsafe fn free(&self, ptr: *mut u8, layout: Layout) {
+        rust_alloc::dealloc(ptr, layout)
+    }
+}
+
+// Live memory-accounting counters shared with a TrackingAllocator. Cheap to
+// clone (Arc-backed); hold a clone alongside the allocator you pass to an
+// Instance/Session and call snapshot() to profile a model's runtime
+// footprint or detect leaks across repeated invocations.
+#[derive(Clone, Default)]
+pub struct AllocatorStats {
+    inner: Arc<AllocatorStatsInner>,
+}
+
+#[derive(Default)]
+struct AllocatorStatsInner {
+    current_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    total_allocations: AtomicUsize,
+    live_allocations: AtomicUsize,
+}
+
+// A point-in-time copy of a TrackingAllocator's counters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocatorStatsSnapshot {
+    // Bytes currently handed out and not yet freed.
+    pub current_bytes: usize,
+    // High-water mark of current_bytes seen so far.
+    pub peak_bytes: usize,
+    // Total number of allocations made over the allocator's lifetime.
+    pub total_allocations: usize,
+    // Number of allocations made but not yet freed.
+    pub live_allocations: usize,
+}
+
+impl AllocatorStats {
+    fn on_alloc(&self, bytes: usize) {
+        let current = self.inner.current_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.inner.peak_bytes.fetch_max(current, Ordering::Relaxed);
+        self.inner.total_allocations.fetch_add(1, Ordering::Relaxed);
+        self.inner.live_allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_free(&self, bytes: usize) {
+        self.inner.current_bytes.fetch_sub(bytes, Ordering::Relaxed);
+        self.inner.live_allocations.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn on_realloc(&self, old_bytes: usize, new_bytes: usize) {
+        if new_bytes >= old_bytes {
+            let current = self
+                .inner
+                .current_bytes
+                .fetch_add(new_bytes - old_bytes, Ordering::Relaxed)
+                + (new_bytes - old_bytes);
+            self.inner.peak_bytes.fetch_max(current, Ordering::Relaxed);
+        } else {
+            self.inner
+                .current_bytes
+                .fetch_sub(old_bytes - new_bytes, Ordering::Relaxed);
+        }
+        self.inner.total_allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> AllocatorStatsSnapshot {
+        AllocatorStatsSnapshot {
+            current_bytes: self.inner.current_bytes.load(Ordering::Relaxed),
+            peak_bytes: self.inner.peak_bytes.load(Ordering::Relaxed),
+            total_allocations: self.inner.total_allocations.load(Ordering::Relaxed),
+            live_allocations: self.inner.live_allocations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Wraps any IreeAllocator and records live memory statistics for every
+// MALLOC/CALLOC/REALLOC/FREE routed through it. Pass it to
+// Instance::new_with_allocator or Session::create_with_device_and_allocator
+// and keep a clone of its stats() handle to inspect usage while the model runs.
+pub struct TrackingAllocator<A: IreeAllocator = Global> {
+    inner: A,
+    stats: AllocatorStats,
+}
+
+impl TrackingAllocator<Global> {
+    pub fn new() -> Self {
+        Self::with_allocator(Global)
+    }
+}
+
+impl Default for TrackingAllocator<Global> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: IreeAllocator> TrackingAllocator<A> {
+    pub fn with_allocator(inner: A) -> Self {
+        Self {
+            inner,
+            stats: AllocatorStats::default(),
+        }
+    }
+
+    // Returns a cheaply clonable handle to the live counters.
+    pub fn stats(&self) -> AllocatorStats {
+        self.stats.clone()
+    }
+}
+
+unsafe impl<A: IreeAllocator> IreeAllocator for TrackingAllocator<A> {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            self.stats.on_alloc(header_recorded_size(layout));
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, old_layout: Layout, new_layout: Layout) -> *mut u8 {
+        let out = self.inner.realloc(ptr, old_layout, new_layout);
+        if !out.is_null() {
+            self.stats
+                .on_realloc(header_recorded_size(old_layout), header_recorded_size(new_layout));
+        }
+        out
+    }
+
+    unsafe fn free(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.free(ptr, layout);
+        self.stats.on_free(header_recorded_size(layout));
+    }
+}
+
+// trait_allocator_ctl always calls through with the header-inclusive Layout
+// `(size + ALIGN, ALIGN)`, where `size` is also what it writes into the
+// block's size-prefix header. Recover that `size` instead of counting the
+// ALIGN header bytes as live payload.
+fn header_recorded_size(layout: Layout) -> usize {
+    layout.size() - layout.align()
+}
+
 pub(crate) struct Allocator {
     pub(crate) ctx: sys::iree_allocator_t,
+    // Keeps a boxed trait object alive for as long as IREE holds `ctx.self_`.
+    owned: Option<OwnedAllocator>,
+}
+
+// Type-erased owner of a boxed [`IreeAllocator`]; dropped when the [`Allocator`]
+// is, releasing the backing storage `ctx.self_` points at.
+struct OwnedAllocator {
+    ptr: *mut c_void,
+    drop_fn: unsafe fn(*mut c_void),
+}
+
+unsafe fn drop_boxed<A: IreeAllocator>(ptr: *mut c_void) {
+    drop(Box::from_raw(ptr as *mut A));
 }
 
 impl Allocator {
+    pub(crate) fn from_raw(ctx: sys::iree_allocator_t) -> Self {
+        Self { ctx, owned: None }
+    }
+
     pub fn get_global() -> Self {
-        let allocator = sys::iree_allocator_t {
-            self_: std::ptr::null_mut(),
+        Self::from_raw(sys::iree_allocator_t {
+            self_: core::ptr::null_mut(),
             ctl: Some(rust_allocator_ctl),
-        };
-        Self { ctx: allocator }
+        })
     }
 
     pub fn null_allocator() -> Self {
-        let allocator = sys::iree_allocator_t {
-            self_: std::ptr::null_mut(),
+        Self::from_raw(sys::iree_allocator_t {
+            self_: core::ptr::null_mut(),
             ctl: Some(null_allocator_ctl),
-        };
-        Self { ctx: allocator }
+        })
+    }
+
+    // Wraps a user IreeAllocator as an iree_allocator_t. ALIGN sizes and aligns
+    // the prefix header holding each block's size, and bounds the alignment of
+    // the pointers handed back to IREE; pick a larger value for over-aligned
+    // device-staging buffers.
+    pub(crate) fn from_trait<A: IreeAllocator + 'static, const ALIGN: usize>(allocator: A) -> Self {
+        const { assert!(ALIGN >= core::mem::size_of::<usize>() && ALIGN.is_power_of_two()) };
+        let ptr = Box::into_raw(Box::new(allocator)) as *mut c_void;
+        Self {
+            ctx: sys::iree_allocator_t {
+                self_: ptr,
+                ctl: Some(trait_allocator_ctl::<A, ALIGN>),
+            },
+            owned: Some(OwnedAllocator {
+                ptr,
+                drop_fn: drop_boxed::<A>,
+            }),
+        }
+    }
+}
+
+impl Drop for Allocator {
+    fn drop(&mut self) {
+        if let Some(owned) = self.owned.take() {
+            unsafe { (owned.drop_fn)(owned.ptr) }
+        }
     }
 }
 
@@ -132,7 +363,7 @@ unsafe extern "C" fn null_allocator_ctl(
             trace!("null_allocator_ctl: command: {:?}", command);
         }
     }
-    std::ptr::null_mut() as *mut c_void as sys::iree_status_t
+    core::ptr::null_mut() as *mut c_void as sys::iree_status_t
 }
 
 unsafe extern "C" fn rust_allocator_ctl(
@@ -145,10 +376,10 @@ unsafe extern "C" fn rust_allocator_ctl(
     match command {
         sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_MALLOC => {
             let size = (*(params as *const sys::iree_allocator_alloc_params_t)).byte_length;
-            if size > std::isize::MAX as usize {
+            if size > isize::MAX as usize {
                 return Status::from_code(StatusErrorKind::OutOfRange).ctx;
             }
-            let ptr = std::alloc::alloc(std::alloc::Layout::from_size_align_unchecked(
+            let ptr = rust_alloc::alloc(Layout::from_size_align_unchecked(
                 size + ALIGNMENT,
                 ALIGNMENT,
             ));
@@ -158,14 +389,14 @@ unsafe extern "C" fn rust_allocator_ctl(
                 "rust_allocator_ctl: IREE_ALLOCATOR_COMMAND_MALLOC: size: {} -> {:?}",
                 size, *inout_ptr
             );
-            std::ptr::null_mut() as *mut c_void as sys::iree_status_t
+            core::ptr::null_mut() as *mut c_void as sys::iree_status_t
         }
         sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_CALLOC => {
             let size = (*(params as *const sys::iree_allocator_alloc_params_t)).byte_length;
-            if size > std::isize::MAX as usize {
+            if size > isize::MAX as usize {
                 return Status::from_code(StatusErrorKind::OutOfRange).ctx;
             }
-            let ptr = std::alloc::alloc_zeroed(std::alloc::Layout::from_size_align_unchecked(
+            let ptr = rust_alloc::alloc_zeroed(Layout::from_size_align_unchecked(
                 size + ALIGNMENT,
                 ALIGNMENT,
             ));
@@ -175,10 +406,10 @@ unsafe extern "C" fn rust_allocator_ctl(
                 "rust_allocator_ctl: IREE_ALLOCATOR_COMMAND_CALLOC: size: {} -> {:?}",
                 size, *inout_ptr
             );
-            std::ptr::null_mut() as *mut c_void as sys::iree_status_t
+            core::ptr::null_mut() as *mut c_void as sys::iree_status_t
         }
         sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_REALLOC => {
-            if *inout_ptr == std::ptr::null_mut() {
+            if *inout_ptr == core::ptr::null_mut() {
                 // realloc of null is malloc
                 return rust_allocator_ctl(
                     _self_,
@@ -194,19 +425,19 @@ unsafe extern "C" fn rust_allocator_ctl(
                 "rust_allocator_ctl: IREE_ALLOCATOR_COMMAND_REALLOC: {} -> {}",
                 old_size, new_size
             );
-            if new_size > std::isize::MAX as usize {
+            if new_size > isize::MAX as usize {
                 return Status::from_code(StatusErrorKind::OutOfRange).ctx;
             }
-            let ptr = std::alloc::realloc(
+            let ptr = rust_alloc::realloc(
                 ptr as *mut u8,
-                std::alloc::Layout::from_size_align_unchecked(old_size + ALIGNMENT, ALIGNMENT),
+                Layout::from_size_align_unchecked(old_size + ALIGNMENT, ALIGNMENT),
                 new_size + ALIGNMENT,
             );
             unsafe {
                 *(ptr as *mut usize) = new_size;
             }
             *inout_ptr = ptr.wrapping_add(ALIGNMENT) as *mut c_void;
-            std::ptr::null_mut() as *mut c_void as sys::iree_status_t
+            core::ptr::null_mut() as *mut c_void as sys::iree_status_t
         }
         sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_FREE => {
             let ptr = (*inout_ptr).wrapping_sub(ALIGNMENT);
@@ -215,11 +446,87 @@ unsafe extern "C" fn rust_allocator_ctl(
                 "rust_allocator_ctl: IREE_ALLOCATOR_COMMAND_FREE: size: {}->{:p}",
                 size, *inout_ptr
             );
-            std::alloc::dealloc(
+            rust_alloc::dealloc(
                 ptr as *mut u8,
-                std::alloc::Layout::from_size_align_unchecked(size + ALIGNMENT, ALIGNMENT),
+                Layout::from_size_align_unchecked(size + ALIGNMENT, ALIGNMENT),
             );
-            std::ptr::null_mut() as *mut c_void as sys::iree_status_t
+            core::ptr::null_mut() as *mut c_void as sys::iree_status_t
+        }
+        _ => Status::from_code(StatusErrorKind::Unimplemented).ctx,
+    }
+}
+
+// Generic trampoline dispatching `iree_allocator_t` commands onto a boxed
+// [`IreeAllocator`] recovered from `self_`. Like `rust_allocator_ctl`, it keeps
+// the block size in a prefix header so `FREE`/`REALLOC` can rebuild the
+// original `Layout`; `ALIGN` sizes and aligns that header.
+unsafe extern "C" fn trait_allocator_ctl<A: IreeAllocator, const ALIGN: usize>(
+    self_: *mut c_void,
+    command: sys::iree_allocator_command_e,
+    params: *const c_void,
+    inout_ptr: *mut *mut c_void,
+) -> sys::iree_status_t {
+    let allocator = &*(self_ as *const A);
+    match command {
+        sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_MALLOC
+        | sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_CALLOC => {
+            let size = (*(params as *const sys::iree_allocator_alloc_params_t)).byte_length;
+            if size > isize::MAX as usize {
+                return Status::from_code(StatusErrorKind::OutOfRange).ctx;
+            }
+            let ptr = allocator.alloc(Layout::from_size_align_unchecked(size + ALIGN, ALIGN));
+            if ptr.is_null() {
+                return Status::from_code(StatusErrorKind::ResourceExhausted).ctx;
+            }
+            *(ptr as *mut usize) = size;
+            let data = ptr.wrapping_add(ALIGN);
+            if command == sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_CALLOC {
+                core::ptr::write_bytes(data, 0, size);
+            }
+            *inout_ptr = data as *mut c_void;
+            trace!(
+                "trait_allocator_ctl: MALLOC/CALLOC: size: {} -> {:?}",
+                size, *inout_ptr
+            );
+            core::ptr::null_mut() as *mut c_void as sys::iree_status_t
+        }
+        sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_REALLOC => {
+            if (*inout_ptr).is_null() {
+                return trait_allocator_ctl::<A, ALIGN>(
+                    self_,
+                    sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_MALLOC,
+                    params,
+                    inout_ptr,
+                );
+            }
+            let ptr = (*inout_ptr).wrapping_sub(ALIGN) as *mut u8;
+            let old_size = *(ptr as *mut usize);
+            let new_size = (*(params as *const sys::iree_allocator_alloc_params_t)).byte_length;
+            if new_size > isize::MAX as usize {
+                return Status::from_code(StatusErrorKind::OutOfRange).ctx;
+            }
+            let ptr = allocator.realloc(
+                ptr,
+                Layout::from_size_align_unchecked(old_size + ALIGN, ALIGN),
+                Layout::from_size_align_unchecked(new_size + ALIGN, ALIGN),
+            );
+            if ptr.is_null() {
+                return Status::from_code(StatusErrorKind::ResourceExhausted).ctx;
+            }
+            *(ptr as *mut usize) = new_size;
+            *inout_ptr = ptr.wrapping_add(ALIGN) as *mut c_void;
+            trace!(
+                "trait_allocator_ctl: REALLOC: {} -> {}",
+                old_size, new_size
+            );
+            core::ptr::null_mut() as *mut c_void as sys::iree_status_t
+        }
+        sys::iree_allocator_command_e_IREE_ALLOCATOR_COMMAND_FREE => {
+            let ptr = (*inout_ptr).wrapping_sub(ALIGN) as *mut u8;
+            let size = *(ptr as *mut usize);
+            trace!("trait_allocator_ctl: FREE: size: {}->{:p}", size, *inout_ptr);
+            allocator.free(ptr, Layout::from_size_align_unchecked(size + ALIGN, ALIGN));
+            core::ptr::null_mut() as *mut c_void as sys::iree_status_t
         }
         _ => Status::from_code(StatusErrorKind::Unimplemented).ctx,
     }
@@ -260,37 +567,67 @@ impl Status {
     }
 }
 
-impl std::fmt::Debug for StatusError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for StatusError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         Display::fmt(self, f)
     }
 }
 
 impl Display for StatusError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut bufptr = std::ptr::null_mut();
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.format() {
+            Some(buf) => write!(f, "Status: {:?}", buf),
+            None => write!(f, "Status: <failed to convert to string>"),
+        }
+    }
+}
+
+pub struct StatusError {
+    status: Status,
+}
+
+impl StatusError {
+    // Read straight from the raw status via iree_status_code, so callers can
+    // match on the root cause instead of scraping Display.
+    pub fn code(&self) -> StatusErrorKind {
+        StatusErrorKind::from(unsafe { sys::iree_status_code(self.status.ctx) } as sys::iree_status_code_e)
+    }
+
+    // Includes any annotations joined onto the status by Status::chain.
+    // IREE's public C API only exposes the joined payloads pre-formatted
+    // through iree_status_to_string; there's no per-entry (code, message)
+    // walk to expose here, so matching on a specific annotation in the chain
+    // has to be done against this string.
+    pub fn message(&self) -> String {
+        self.format().unwrap_or_default()
+    }
+
+    // Formats the status through `iree_status_to_string`, copying the result
+    // into an owned `String` and releasing the temporary IREE allocation.
+    fn format(&self) -> Option<String> {
+        let mut bufptr = core::ptr::null_mut();
         let allocator = Allocator::get_global();
         let mut size: usize = 0;
         if !(unsafe {
             sys::iree_status_to_string(self.status.ctx, &allocator.ctx, &mut bufptr, &mut size)
         }) {
-            return write!(f, "Status: <failed to convert to string>");
+            return None;
         }
-        let buf =
-            std::str::from_utf8(unsafe { std::slice::from_raw_parts(bufptr as *const u8, size) })
-                .map_err(|_| std::fmt::Error)?;
-        let write_result = write!(f, "Status: {:?}", buf);
+        let owned =
+            core::str::from_utf8(unsafe { core::slice::from_raw_parts(bufptr as *const u8, size) })
+                .ok()
+                .map(|buf| buf.to_string());
         unsafe {
             sys::iree_allocator_free(allocator.ctx, bufptr as *mut _);
         }
-        write_result
+        owned
     }
 }
 
-pub struct StatusError {
-    status: Status,
-}
-
+// `core::error::Error` is not yet stable on the MSRV this crate targets, so the
+// `Error` impl is `std`-only; on bare-metal `StatusError` is still a fully
+// usable `Display`/`Debug` type via the `core::fmt` path above.
+#[cfg(feature = "std")]
 impl std::error::Error for StatusError {}
 
 impl<'a, 'b> Drop for Status {
@@ -377,3 +714,56 @@ impl From<StatusErrorKind> for sys::iree_status_code_t {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALIGN: usize = 16;
+
+    // Mirrors the header-inclusive Layout trait_allocator_ctl builds: the
+    // wrapped allocator always sees size + ALIGN, aligned to ALIGN.
+    fn header_inclusive_layout(logical_size: usize) -> Layout {
+        Layout::from_size_align(logical_size + ALIGN, ALIGN).unwrap()
+    }
+
+    #[test]
+    fn tracking_allocator_counts_alloc_realloc_free() {
+        let tracker = TrackingAllocator::new();
+
+        let ptr = tracker.alloc(header_inclusive_layout(64));
+        assert!(!ptr.is_null());
+        let snapshot = tracker.stats().snapshot();
+        assert_eq!(snapshot.current_bytes, 64);
+        assert_eq!(snapshot.peak_bytes, 64);
+        assert_eq!(snapshot.total_allocations, 1);
+        assert_eq!(snapshot.live_allocations, 1);
+
+        let ptr = unsafe {
+            tracker.realloc(ptr, header_inclusive_layout(64), header_inclusive_layout(256))
+        };
+        assert!(!ptr.is_null());
+        let snapshot = tracker.stats().snapshot();
+        assert_eq!(snapshot.current_bytes, 256);
+        assert_eq!(snapshot.peak_bytes, 256);
+        assert_eq!(snapshot.total_allocations, 2);
+        assert_eq!(snapshot.live_allocations, 1);
+
+        let ptr = unsafe {
+            tracker.realloc(ptr, header_inclusive_layout(256), header_inclusive_layout(32))
+        };
+        assert!(!ptr.is_null());
+        let snapshot = tracker.stats().snapshot();
+        assert_eq!(snapshot.current_bytes, 32);
+        assert_eq!(snapshot.peak_bytes, 256);
+        assert_eq!(snapshot.total_allocations, 3);
+        assert_eq!(snapshot.live_allocations, 1);
+
+        unsafe { tracker.free(ptr, header_inclusive_layout(32)) };
+        let snapshot = tracker.stats().snapshot();
+        assert_eq!(snapshot.current_bytes, 0);
+        assert_eq!(snapshot.peak_bytes, 256);
+        assert_eq!(snapshot.total_allocations, 3);
+        assert_eq!(snapshot.live_allocations, 0);
+    }
+}